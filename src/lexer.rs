@@ -6,6 +6,10 @@ pub struct Lexer<'a> {
     // For times when the lexer should treat newlines
     // as spaces
     pub newline_is_space: bool,
+
+    // What the most recent scan found unterminated at the end of `source`,
+    // if anything. See `incomplete_reason`.
+    last_incomplete: Option<Incomplete>,
 }
 
 #[derive(Debug)]
@@ -18,47 +22,126 @@ pub enum TokenType {
     SimpleString,
     String,
     Dot,
-    DotDot,
     Dollar,
     Variable,
     Pipe,
-    PipePipe,
     Colon,
     Semicolon,
     Plus,
-    PlusPlus,
     Dash,
     Exclamation,
     Asterisk,
-    AsteriskAsterisk,
     ForwardSlash,
-    ForwardSlashForwardSlash,
     Equals,
-    EqualsEquals,
-    EqualsTilde,
-    ExclamationTilde,
-    ExclamationEquals,
     LParen,
     LSquare,
     LCurly,
     LessThan,
-    LessThanEqual,
     RParen,
     RSquare,
     RCurly,
     GreaterThan,
-    GreaterThanEqual,
     Ampersand,
-    AmpersandAmpersand,
+    Tilde,
     Bareword,
 }
 
+/// Whether a single-character punctuation token sits flush against the next
+/// symbol character (`Joint`, as in `==`) or has something else — whitespace,
+/// a different kind of token, or end of input — after it (`Alone`, as in
+/// `= =` or a lone `=`).
+///
+/// The lexer only ever emits one symbol character at a time, and it's up to
+/// the parser to fuse adjacent `Joint` tokens into multi-character
+/// operators. That keeps `lex_symbol` a flat, one-arm-per-byte match that
+/// never needs a new branch when an operator made of existing symbol
+/// characters is added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Spacing {
+    Joint,
+    Alone,
+}
+
 #[derive(Debug)]
 pub struct Token<'a> {
     pub token_type: TokenType,
     pub contents: &'a [u8],
     pub span_start: usize,
     pub span_end: usize,
+    /// `Some` for punctuation tokens produced by `lex_symbol`, describing
+    /// whether the next byte is also a symbol character with no whitespace
+    /// in between. `None` for every other token kind.
+    pub spacing: Option<Spacing>,
+    /// `Some` for `Number` tokens, describing how the literal was written so
+    /// callers don't have to re-scan `contents` to tell an int from a float
+    /// or figure out its radix. `None` for every other token kind.
+    pub number_kind: Option<NumberKind>,
+}
+
+/// The radix an integer literal was written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Radix {
+    Decimal,
+    Hex,
+    Octal,
+    Binary,
+}
+
+/// Whether a `Number` token is an integer (and in what radix) or a float.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberKind {
+    Int(Radix),
+    Float,
+}
+
+/// What a scan found unterminated at the end of the input, reported by
+/// `Lexer::incomplete_reason` alongside the `LexError` that scanning it
+/// produced. Lets a REPL front end tell "this buffer is incomplete, read
+/// another line" apart from an error that's wrong no matter how much more
+/// input follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Incomplete {
+    UnterminatedDoubleString,
+    UnterminatedSingleString,
+    UnterminatedBareword,
+    OpenDelimiter(Delimiter),
+}
+
+/// The kind of problem encountered while scanning a single token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LexErrorKind {
+    /// A `"..."` string ran off the end of the input before its closing quote.
+    UnterminatedString,
+    /// A `'...'` string ran off the end of the input before its closing quote.
+    UnterminatedSingleQuotedString,
+    /// A `` `...` `` quoted bareword ran off the end of the input before its closing backtick.
+    UnterminatedQuotedBareword,
+    /// A byte was seen where `is_symbol` said it should be handled, but no
+    /// symbol arm claims it.
+    UnexpectedByte,
+    /// A `0x`/`0o`/`0b` radix prefix was not followed by at least one digit
+    /// valid for that radix.
+    MalformedRadixNumber,
+    /// A closing delimiter was seen that didn't match the delimiter of the
+    /// group it closed (or there was no open group at all).
+    MismatchedCloseDelimiter(Delimiter),
+    /// A group was opened but the input ended before its closing delimiter
+    /// was found.
+    UnterminatedGroup(Delimiter),
+    /// `(`/`[`/`{` groups nested past `MAX_GROUP_NESTING_DEPTH`.
+    NestingTooDeep,
+}
+
+/// A recoverable lexing failure, carrying the byte span where it was detected
+/// so callers can point a diagnostic at the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub span_start: usize,
+    pub span_end: usize,
+    /// For delimiter errors, the span of the opening delimiter the error is
+    /// relative to, so a diagnostic can point at both sides of the mismatch.
+    pub opening_span: Option<(usize, usize)>,
 }
 
 fn is_symbol(b: u8) -> bool {
@@ -69,19 +152,77 @@ fn is_symbol(b: u8) -> bool {
     .contains(&b)
 }
 
+/// Scans from offset 1 (past the opening delimiter byte already checked by
+/// the caller) for `closing`, with no escape handling. Returns the offset
+/// just past `closing`, and whether it was actually found before running off
+/// the end of `source` — shared by the single-quoted-string and
+/// quoted-bareword scanners so both report the same kind of unterminated-input
+/// failure instead of one of them silently truncating.
+fn scan_to_closing_byte(source: &[u8], closing: u8) -> (usize, bool) {
+    let mut offset = 1;
+    while offset < source.len() {
+        if source[offset] == closing {
+            return (offset + 1, true);
+        }
+        offset += 1;
+    }
+    (offset, false)
+}
+
+/// Consumes a run of digits (as judged by `is_digit`) starting at `start`,
+/// allowing `_` separators as long as a digit comes immediately before and
+/// after each one. A `_` that would be leading, trailing, or doubled is left
+/// unconsumed instead of erroring, so it falls out as the start of the next
+/// token. Returns the offset just past the last digit consumed, which equals
+/// `start` if no digit was found there.
+fn scan_digit_run(bytes: &[u8], start: usize, is_digit: impl Fn(u8) -> bool) -> usize {
+    let mut offset = start;
+    while offset < bytes.len() {
+        let is_separator = bytes[offset] == b'_'
+            && offset > start
+            && is_digit(bytes[offset - 1])
+            && bytes.get(offset + 1).is_some_and(|&b| is_digit(b));
+        if is_digit(bytes[offset]) || is_separator {
+            offset += 1;
+        } else {
+            break;
+        }
+    }
+    offset
+}
+
 impl<'a> Lexer<'a> {
     pub fn new(source: &'a [u8], span_offset: usize) -> Self {
         Self {
             source,
             span_offset,
             newline_is_space: false,
+            last_incomplete: None,
         }
     }
 
-    pub fn lex_quoted_string(&mut self) -> Option<Token<'a>> {
+    /// Resumes scanning from `source`, as when a REPL appends a new line to
+    /// a buffer that previously came back with `incomplete_reason().is_some()`
+    /// and wants to retry lexing the combined buffer from `span_offset`.
+    pub fn feed(&mut self, source: &'a [u8], span_offset: usize) {
+        self.source = source;
+        self.span_offset = span_offset;
+        self.last_incomplete = None;
+    }
+
+    /// What the most recent call to `next`/`peek` (or `token_stream`, for an
+    /// open delimiter) found unterminated at the end of the input, if anything. A REPL front end can use this to tell
+    /// "keep reading, the user isn't done with this string/bracket yet" apart
+    /// from a genuine syntax error.
+    pub fn incomplete_reason(&self) -> Option<Incomplete> {
+        self.last_incomplete
+    }
+
+    pub fn lex_quoted_string(&mut self) -> Result<Option<Token<'a>>, LexError> {
         let span_start = self.span_offset;
         let mut token_offset = 1;
         let mut is_escaped = false;
+        let mut terminated = false;
         while token_offset < self.source.len() {
             if is_escaped {
                 is_escaped = false;
@@ -89,33 +230,49 @@ impl<'a> Lexer<'a> {
                 is_escaped = true;
             } else if self.source[token_offset] == b'"' {
                 token_offset += 1;
+                terminated = true;
                 break;
             }
             token_offset += 1;
         }
 
+        if !terminated {
+            self.last_incomplete = Some(Incomplete::UnterminatedDoubleString);
+            return Err(LexError {
+                kind: LexErrorKind::UnterminatedString,
+                span_start,
+                span_end: span_start + token_offset,
+                opening_span: None,
+            });
+        }
+
         self.span_offset += token_offset;
 
         let contents = &self.source[..token_offset];
         self.source = &self.source[token_offset..];
 
-        Some(Token {
+        Ok(Some(Token {
             token_type: TokenType::String,
             contents,
             span_start,
             span_end: self.span_offset,
-        })
+            spacing: None,
+            number_kind: None,
+        }))
     }
 
-    pub fn lex_single_quoted_string(&mut self) -> Option<Token<'a>> {
+    pub fn lex_single_quoted_string(&mut self) -> Result<Option<Token<'a>>, LexError> {
         let span_start = self.span_offset;
-        let mut token_offset = 1;
-        while token_offset < self.source.len() {
-            if self.source[token_offset] == b'\'' {
-                token_offset += 1;
-                break;
-            }
-            token_offset += 1;
+        let (token_offset, terminated) = scan_to_closing_byte(self.source, b'\'');
+
+        if !terminated {
+            self.last_incomplete = Some(Incomplete::UnterminatedSingleString);
+            return Err(LexError {
+                kind: LexErrorKind::UnterminatedSingleQuotedString,
+                span_start,
+                span_end: span_start + token_offset,
+                opening_span: None,
+            });
         }
 
         self.span_offset += token_offset;
@@ -123,23 +280,28 @@ impl<'a> Lexer<'a> {
         let contents = &self.source[..token_offset];
         self.source = &self.source[token_offset..];
 
-        Some(Token {
+        Ok(Some(Token {
             token_type: TokenType::SimpleString,
             contents,
             span_start,
             span_end: self.span_offset,
-        })
+            spacing: None,
+            number_kind: None,
+        }))
     }
 
-    pub fn lex_quoted_bareword(&mut self) -> Option<Token<'a>> {
+    pub fn lex_quoted_bareword(&mut self) -> Result<Option<Token<'a>>, LexError> {
         let span_start = self.span_offset;
-        let mut token_offset = 1;
-        while token_offset < self.source.len() {
-            if self.source[token_offset] == b'`' {
-                token_offset += 1;
-                break;
-            }
-            token_offset += 1;
+        let (token_offset, terminated) = scan_to_closing_byte(self.source, b'`');
+
+        if !terminated {
+            self.last_incomplete = Some(Incomplete::UnterminatedBareword);
+            return Err(LexError {
+                kind: LexErrorKind::UnterminatedQuotedBareword,
+                span_start,
+                span_end: span_start + token_offset,
+                opening_span: None,
+            });
         }
 
         self.span_offset += token_offset - 1;
@@ -147,22 +309,79 @@ impl<'a> Lexer<'a> {
         let contents = &self.source[1..(token_offset - 1)];
         self.source = &self.source[token_offset..];
 
-        Some(Token {
+        Ok(Some(Token {
             token_type: TokenType::Bareword,
             contents,
             span_start: span_start + 1,
             span_end: self.span_offset,
-        })
+            spacing: None,
+            number_kind: None,
+        }))
     }
 
-    pub fn lex_number(&mut self) -> Option<Token<'a>> {
+    pub fn lex_number(&mut self) -> Result<Option<Token<'a>>, LexError> {
         let span_start = self.span_offset;
-        let mut token_offset = 0;
-        while token_offset < self.source.len() {
-            if !self.source[token_offset].is_ascii_digit() {
-                break;
+
+        if self.source[0] == b'0' && matches!(self.source.get(1), Some(b'x' | b'o' | b'b')) {
+            let (radix, is_radix_digit): (Radix, fn(u8) -> bool) = match self.source[1] {
+                b'x' => (Radix::Hex, |b: u8| b.is_ascii_hexdigit()),
+                b'o' => (Radix::Octal, |b: u8| (b'0'..=b'7').contains(&b)),
+                b'b' => (Radix::Binary, |b: u8| b == b'0' || b == b'1'),
+                _ => unreachable!("guarded by the match above"),
+            };
+
+            let token_offset = scan_digit_run(self.source, 2, is_radix_digit);
+            if token_offset == 2 {
+                return Err(LexError {
+                    kind: LexErrorKind::MalformedRadixNumber,
+                    span_start,
+                    span_end: span_start + self.source.len().min(2),
+                    opening_span: None,
+                });
+            }
+
+            self.span_offset += token_offset;
+            let contents = &self.source[..token_offset];
+            self.source = &self.source[token_offset..];
+
+            return Ok(Some(Token {
+                token_type: TokenType::Number,
+                contents,
+                span_start,
+                span_end: self.span_offset,
+                spacing: None,
+                number_kind: Some(NumberKind::Int(radix)),
+            }));
+        }
+
+        let mut token_offset = scan_digit_run(self.source, 0, |b| b.is_ascii_digit());
+        let mut number_kind = NumberKind::Int(Radix::Decimal);
+
+        // Only consume the '.' as a decimal point if a digit follows it, so
+        // that the `..` range operator is never swallowed here.
+        if self.source.get(token_offset) == Some(&b'.')
+            && self
+                .source
+                .get(token_offset + 1)
+                .is_some_and(u8::is_ascii_digit)
+        {
+            token_offset = scan_digit_run(self.source, token_offset + 1, |b| b.is_ascii_digit());
+            number_kind = NumberKind::Float;
+        }
+
+        if matches!(self.source.get(token_offset), Some(b'e' | b'E')) {
+            let mut exponent_start = token_offset + 1;
+            if matches!(self.source.get(exponent_start), Some(b'+' | b'-')) {
+                exponent_start += 1;
+            }
+            if self
+                .source
+                .get(exponent_start)
+                .is_some_and(u8::is_ascii_digit)
+            {
+                token_offset = scan_digit_run(self.source, exponent_start, |b| b.is_ascii_digit());
+                number_kind = NumberKind::Float;
             }
-            token_offset += 1;
         }
 
         self.span_offset += token_offset;
@@ -170,15 +389,17 @@ impl<'a> Lexer<'a> {
         let contents = &self.source[..token_offset];
         self.source = &self.source[token_offset..];
 
-        Some(Token {
+        Ok(Some(Token {
             token_type: TokenType::Number,
             contents,
             span_start,
             span_end: self.span_offset,
-        })
+            spacing: None,
+            number_kind: Some(number_kind),
+        }))
     }
 
-    pub fn lex_space(&mut self) -> Option<Token<'a>> {
+    pub fn lex_space(&mut self) -> Result<Option<Token<'a>>, LexError> {
         let span_start = self.span_offset;
         let mut token_offset = 0;
         let whitespace: &[u8] = if self.newline_is_space {
@@ -197,30 +418,34 @@ impl<'a> Lexer<'a> {
         let contents = &self.source[..token_offset];
         self.source = &self.source[token_offset..];
 
-        Some(Token {
+        Ok(Some(Token {
             token_type: TokenType::Space,
             contents,
             span_start,
             span_end: self.span_offset,
-        })
+            spacing: None,
+            number_kind: None,
+        }))
     }
 
-    pub fn lex_newline(&mut self) -> Option<Token<'a>> {
+    pub fn lex_newline(&mut self) -> Result<Option<Token<'a>>, LexError> {
         let span_start = self.span_offset;
         self.span_offset += 1;
 
         let contents = &self.source[..1];
         self.source = &self.source[1..];
 
-        Some(Token {
+        Ok(Some(Token {
             token_type: TokenType::Newline,
             contents,
             span_start,
             span_end: span_start + 1,
-        })
+            spacing: None,
+            number_kind: None,
+        }))
     }
 
-    pub fn lex_variable(&mut self) -> Option<Token<'a>> {
+    pub fn lex_variable(&mut self) -> Result<Option<Token<'a>>, LexError> {
         let span_start = self.span_offset;
         self.span_offset += 1;
 
@@ -237,15 +462,17 @@ impl<'a> Lexer<'a> {
         let contents = &self.source[..token_offset];
         self.source = &self.source[token_offset..];
 
-        Some(Token {
+        Ok(Some(Token {
             token_type: TokenType::Variable,
             contents,
             span_start,
             span_end: self.span_offset,
-        })
+            spacing: None,
+            number_kind: None,
+        }))
     }
 
-    pub fn lex_comment(&mut self) -> Option<Token<'a>> {
+    pub fn lex_comment(&mut self) -> Result<Option<Token<'a>>, LexError> {
         let span_start = self.span_offset;
         self.span_offset += 1;
 
@@ -260,283 +487,74 @@ impl<'a> Lexer<'a> {
         let contents = &self.source[..token_offset];
         self.source = &self.source[token_offset..];
 
-        Some(Token {
+        Ok(Some(Token {
             token_type: TokenType::Comment,
             contents,
             span_start,
             span_end: self.span_offset,
-        })
+            spacing: None,
+            number_kind: None,
+        }))
     }
 
-    pub fn lex_symbol(&mut self) -> Option<Token<'a>> {
+    pub fn lex_symbol(&mut self) -> Result<Option<Token<'a>>, LexError> {
         let span_start = self.span_offset;
 
-        let result = match self.source[0] {
-            b'(' => Token {
-                token_type: TokenType::LParen,
-                contents: &self.source[..1],
-                span_start,
-                span_end: span_start + 1,
-            },
-            b'[' => Token {
-                token_type: TokenType::LSquare,
-                contents: &self.source[..1],
-                span_start,
-                span_end: span_start + 1,
-            },
-            b'{' => Token {
-                token_type: TokenType::LCurly,
-                contents: &self.source[..1],
-                span_start,
-                span_end: span_start + 1,
-            },
-            b'<' => {
-                if self.source.len() > 1 && self.source[1] == b'=' {
-                    Token {
-                        token_type: TokenType::LessThanEqual,
-                        contents: &self.source[..2],
-                        span_start,
-                        span_end: span_start + 2,
-                    }
-                } else {
-                    Token {
-                        token_type: TokenType::LessThan,
-                        contents: &self.source[..1],
-                        span_start,
-                        span_end: span_start + 1,
-                    }
-                }
-            }
-            b')' => Token {
-                token_type: TokenType::RParen,
-                contents: &self.source[..1],
-                span_start,
-                span_end: span_start + 1,
-            },
-            b']' => Token {
-                token_type: TokenType::RSquare,
-                contents: &self.source[..1],
-                span_start,
-                span_end: span_start + 1,
-            },
-            b'}' => Token {
-                token_type: TokenType::RCurly,
-                contents: &self.source[..1],
-                span_start,
-                span_end: span_start + 1,
-            },
-            b'>' => {
-                if self.source.len() > 1 && self.source[1] == b'=' {
-                    Token {
-                        token_type: TokenType::GreaterThanEqual,
-                        contents: &self.source[..2],
-                        span_start,
-                        span_end: span_start + 2,
-                    }
-                } else {
-                    Token {
-                        token_type: TokenType::GreaterThan,
-                        contents: &self.source[..1],
-                        span_start,
-                        span_end: span_start + 1,
-                    }
-                }
-            }
-            b'+' => {
-                if self.source.len() > 1 && self.source[1] == b'+' {
-                    Token {
-                        token_type: TokenType::PlusPlus,
-                        contents: &self.source[..2],
-                        span_start,
-                        span_end: span_start + 2,
-                    }
-                } else {
-                    Token {
-                        token_type: TokenType::Plus,
-                        contents: &self.source[..1],
-                        span_start,
-                        span_end: span_start + 1,
-                    }
-                }
-            }
-            b'-' => Token {
-                token_type: TokenType::Dash,
-                contents: &self.source[..1],
-                span_start,
-                span_end: span_start + 1,
-            },
-            b'*' => {
-                if self.source.len() > 1 && self.source[1] == b'*' {
-                    Token {
-                        token_type: TokenType::AsteriskAsterisk,
-                        contents: &self.source[..2],
-                        span_start,
-                        span_end: span_start + 2,
-                    }
-                } else {
-                    Token {
-                        token_type: TokenType::Asterisk,
-                        contents: &self.source[..1],
-                        span_start,
-                        span_end: span_start + 1,
-                    }
-                }
-            }
-            b'/' => {
-                if self.source.len() > 1 && self.source[1] == b'/' {
-                    Token {
-                        token_type: TokenType::ForwardSlashForwardSlash,
-                        contents: &self.source[..2],
-                        span_start,
-                        span_end: span_start + 2,
-                    }
-                } else {
-                    Token {
-                        token_type: TokenType::ForwardSlash,
-                        contents: &self.source[..1],
-                        span_start,
-                        span_end: span_start + 1,
-                    }
-                }
-            }
-            b'=' => {
-                if self.source.len() > 1 && self.source[1] == b'=' {
-                    Token {
-                        token_type: TokenType::EqualsEquals,
-                        contents: &self.source[..2],
-                        span_start,
-                        span_end: span_start + 2,
-                    }
-                } else if self.source.len() > 1 && self.source[1] == b'~' {
-                    Token {
-                        token_type: TokenType::EqualsTilde,
-                        contents: &self.source[..2],
-                        span_start,
-                        span_end: span_start + 2,
-                    }
-                } else {
-                    Token {
-                        token_type: TokenType::Equals,
-                        contents: &self.source[..1],
-                        span_start,
-                        span_end: span_start + 1,
-                    }
-                }
-            }
-            b':' => Token {
-                token_type: TokenType::Colon,
-                contents: &self.source[..1],
-                span_start,
-                span_end: span_start + 1,
-            },
-            b';' => Token {
-                token_type: TokenType::Semicolon,
-                contents: &self.source[..1],
-                span_start,
-                span_end: span_start + 1,
-            },
-            b'.' => {
-                if self.source.len() > 1 && self.source[1] == b'.' {
-                    Token {
-                        token_type: TokenType::DotDot,
-                        contents: &self.source[..2],
-                        span_start,
-                        span_end: span_start + 2,
-                    }
-                } else {
-                    Token {
-                        token_type: TokenType::Dot,
-                        contents: &self.source[..1],
-                        span_start,
-                        span_end: span_start + 1,
-                    }
-                }
-            }
-            b'!' => {
-                if self.source.len() > 1 && self.source[1] == b'=' {
-                    Token {
-                        token_type: TokenType::ExclamationEquals,
-                        contents: &self.source[..2],
-                        span_start,
-                        span_end: span_start + 2,
-                    }
-                } else if self.source.len() > 1 && self.source[1] == b'~' {
-                    Token {
-                        token_type: TokenType::ExclamationTilde,
-                        contents: &self.source[..2],
-                        span_start,
-                        span_end: span_start + 2,
-                    }
-                } else {
-                    Token {
-                        token_type: TokenType::Exclamation,
-                        contents: &self.source[..1],
-                        span_start,
-                        span_end: span_start + 1,
-                    }
-                }
-            }
-            b'$' => Token {
-                token_type: TokenType::Dollar,
-                contents: &self.source[..1],
-                span_start,
-                span_end: span_start + 1,
-            },
-            b'|' => {
-                if self.source.len() > 1 && self.source[1] == b'|' {
-                    Token {
-                        token_type: TokenType::PipePipe,
-                        contents: &self.source[..2],
-                        span_start,
-                        span_end: span_start + 2,
-                    }
-                } else {
-                    Token {
-                        token_type: TokenType::Pipe,
-                        contents: &self.source[..1],
-                        span_start,
-                        span_end: span_start + 1,
-                    }
-                }
-            }
-            b'&' => {
-                if self.source.len() > 1 && self.source[1] == b'&' {
-                    Token {
-                        token_type: TokenType::AmpersandAmpersand,
-                        contents: &self.source[..2],
-                        span_start,
-                        span_end: span_start + 2,
-                    }
-                } else {
-                    Token {
-                        token_type: TokenType::Ampersand,
-                        contents: &self.source[..1],
-                        span_start,
-                        span_end: span_start + 1,
-                    }
-                }
-            }
-            b',' => Token {
-                token_type: TokenType::Comma,
-                contents: &self.source[..1],
-                span_start,
-                span_end: span_start + 1,
-            },
-            x => {
-                panic!(
-                    "Internal compiler error: symbol character mismatched in lexer: {}",
-                    x as char
-                )
+        let token_type = match self.source[0] {
+            b'(' => TokenType::LParen,
+            b'[' => TokenType::LSquare,
+            b'{' => TokenType::LCurly,
+            b'<' => TokenType::LessThan,
+            b')' => TokenType::RParen,
+            b']' => TokenType::RSquare,
+            b'}' => TokenType::RCurly,
+            b'>' => TokenType::GreaterThan,
+            b'+' => TokenType::Plus,
+            b'-' => TokenType::Dash,
+            b'*' => TokenType::Asterisk,
+            b'/' => TokenType::ForwardSlash,
+            b'=' => TokenType::Equals,
+            b':' => TokenType::Colon,
+            b';' => TokenType::Semicolon,
+            b'.' => TokenType::Dot,
+            b'!' => TokenType::Exclamation,
+            b'$' => TokenType::Dollar,
+            b'|' => TokenType::Pipe,
+            b'&' => TokenType::Ampersand,
+            b'~' => TokenType::Tilde,
+            b',' => TokenType::Comma,
+            _ => {
+                return Err(LexError {
+                    kind: LexErrorKind::UnexpectedByte,
+                    span_start,
+                    span_end: span_start + 1,
+                    opening_span: None,
+                })
             }
         };
 
-        self.span_offset = result.span_end;
-        self.source = &self.source[(result.span_end - span_start)..];
+        let spacing = if self.source.len() > 1 && is_symbol(self.source[1]) {
+            Spacing::Joint
+        } else {
+            Spacing::Alone
+        };
 
-        Some(result)
+        let span_end = span_start + 1;
+        let contents = &self.source[..1];
+        self.span_offset = span_end;
+        self.source = &self.source[1..];
+
+        Ok(Some(Token {
+            token_type,
+            contents,
+            span_start,
+            span_end,
+            spacing: Some(spacing),
+            number_kind: None,
+        }))
     }
 
-    pub fn lex_bareword(&mut self) -> Option<Token<'a>> {
+    pub fn lex_bareword(&mut self) -> Result<Option<Token<'a>>, LexError> {
         let span_start = self.span_offset;
         let mut token_offset = 0;
         while token_offset < self.source.len() {
@@ -559,17 +577,19 @@ impl<'a> Lexer<'a> {
         let contents = &self.source[..token_offset];
         self.source = &self.source[token_offset..];
 
-        Some(Token {
+        Ok(Some(Token {
             token_type: TokenType::Bareword,
             contents,
             span_start,
             span_end: self.span_offset,
-        })
+            spacing: None,
+            number_kind: None,
+        }))
     }
 }
 
 impl<'a> Lexer<'a> {
-    pub fn peek(&mut self) -> Option<Token<'a>> {
+    pub fn peek(&mut self) -> Result<Option<Token<'a>>, LexError> {
         let prev_offset = self.span_offset;
         let prev_source = self.source;
         let output = self.next();
@@ -579,9 +599,11 @@ impl<'a> Lexer<'a> {
         output
     }
 
-    pub fn next(&mut self) -> Option<Token<'a>> {
+    pub fn next(&mut self) -> Result<Option<Token<'a>>, LexError> {
+        self.last_incomplete = None;
+
         if self.source.is_empty() {
-            None
+            Ok(None)
         } else if self.source[0].is_ascii_digit() {
             self.lex_number()
         } else if self.source[0] == b'"' {
@@ -609,3 +631,426 @@ impl<'a> Lexer<'a> {
         }
     }
 }
+
+/// Which bracket pair a `Group` is delimited by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delimiter {
+    Paren,
+    Bracket,
+    Brace,
+}
+
+fn opening_delimiter(token_type: &TokenType) -> Option<Delimiter> {
+    match token_type {
+        TokenType::LParen => Some(Delimiter::Paren),
+        TokenType::LSquare => Some(Delimiter::Bracket),
+        TokenType::LCurly => Some(Delimiter::Brace),
+        _ => None,
+    }
+}
+
+fn closing_delimiter(token_type: &TokenType) -> Option<Delimiter> {
+    match token_type {
+        TokenType::RParen => Some(Delimiter::Paren),
+        TokenType::RSquare => Some(Delimiter::Bracket),
+        TokenType::RCurly => Some(Delimiter::Brace),
+        _ => None,
+    }
+}
+
+/// A balanced run of source wrapped in a matching pair of delimiters, with
+/// everything in between re-nested into `tokens`.
+#[derive(Debug)]
+pub struct Group<'a> {
+    pub delimiter: Delimiter,
+    pub tokens: Vec<TokenTree<'a>>,
+    pub open_span: (usize, usize),
+    pub close_span: (usize, usize),
+}
+
+/// A single leaf token or a balanced, recursively-nested group of them.
+///
+/// Turns the flat, unbalanced stream from `Lexer::next` into a tree where
+/// every `(`/`[`/`{` is already paired with its matching closer, so a
+/// consumer never has to track bracket depth itself.
+#[derive(Debug)]
+pub enum TokenTree<'a> {
+    Token(Token<'a>),
+    Group(Group<'a>),
+}
+
+#[derive(Clone, Copy)]
+enum GroupContext {
+    TopLevel,
+    Delimited(Delimiter, (usize, usize)),
+}
+
+/// How deeply `(`/`[`/`{` groups may nest before `parse_token_trees` gives up
+/// with a `LexError` instead of recursing further. Bounds stack usage on
+/// adversarial input (e.g. tens of thousands of unclosed `(`), which would
+/// otherwise overflow the stack before ever reaching an unterminated-group
+/// error.
+const MAX_GROUP_NESTING_DEPTH: usize = 256;
+
+/// Result of parsing one nesting level in `parse_token_trees`: the trees
+/// gathered at this depth, plus the closing delimiter's span when `context`
+/// was `Delimited` (always `Some` in that case, `None` at top level).
+struct ParsedGroup<'a> {
+    trees: Vec<TokenTree<'a>>,
+    close_span: Option<(usize, usize)>,
+}
+
+fn parse_token_trees<'a>(
+    lexer: &mut Lexer<'a>,
+    context: GroupContext,
+    depth: usize,
+) -> Result<ParsedGroup<'a>, LexError> {
+    let mut trees = Vec::new();
+
+    loop {
+        let Some(token) = lexer.next()? else {
+            return match context {
+                GroupContext::TopLevel => Ok(ParsedGroup {
+                    trees,
+                    close_span: None,
+                }),
+                GroupContext::Delimited(delimiter, open_span) => {
+                    lexer.last_incomplete = Some(Incomplete::OpenDelimiter(delimiter));
+                    Err(LexError {
+                        kind: LexErrorKind::UnterminatedGroup(delimiter),
+                        span_start: open_span.0,
+                        span_end: open_span.1,
+                        opening_span: Some(open_span),
+                    })
+                }
+            };
+        };
+
+        if let Some(delimiter) = opening_delimiter(&token.token_type) {
+            let open_span = (token.span_start, token.span_end);
+            if depth >= MAX_GROUP_NESTING_DEPTH {
+                return Err(LexError {
+                    kind: LexErrorKind::NestingTooDeep,
+                    span_start: open_span.0,
+                    span_end: open_span.1,
+                    opening_span: None,
+                });
+            }
+            let inner = parse_token_trees(
+                lexer,
+                GroupContext::Delimited(delimiter, open_span),
+                depth + 1,
+            )?;
+            let close_span = inner
+                .close_span
+                .expect("a Delimited context always returns its close span or errors");
+            trees.push(TokenTree::Group(Group {
+                delimiter,
+                tokens: inner.trees,
+                open_span,
+                close_span,
+            }));
+            continue;
+        }
+
+        if let Some(delimiter) = closing_delimiter(&token.token_type) {
+            let close_span = (token.span_start, token.span_end);
+            return match context {
+                GroupContext::Delimited(expected, _) if expected == delimiter => Ok(ParsedGroup {
+                    trees,
+                    close_span: Some(close_span),
+                }),
+                GroupContext::Delimited(_, open_span) => Err(LexError {
+                    kind: LexErrorKind::MismatchedCloseDelimiter(delimiter),
+                    span_start: close_span.0,
+                    span_end: close_span.1,
+                    opening_span: Some(open_span),
+                }),
+                GroupContext::TopLevel => Err(LexError {
+                    kind: LexErrorKind::MismatchedCloseDelimiter(delimiter),
+                    span_start: close_span.0,
+                    span_end: close_span.1,
+                    opening_span: None,
+                }),
+            };
+        }
+
+        trees.push(TokenTree::Token(token));
+    }
+}
+
+/// Lexes the rest of `lexer`'s input into a tree of balanced token groups,
+/// the `TokenTree` equivalent of `Lexer::next`'s flat token stream.
+///
+/// Takes the `Lexer` by reference rather than owning it so that, on
+/// `Err`, the caller can still call `lexer.incomplete_reason()` to tell an
+/// open string/bareword/delimiter (keep reading, e.g. in a REPL) apart from
+/// a hard syntax error.
+pub fn token_stream<'a>(lexer: &mut Lexer<'a>) -> Result<Vec<TokenTree<'a>>, LexError> {
+    Ok(parse_token_trees(lexer, GroupContext::TopLevel, 0)?.trees)
+}
+
+/// A human-facing position within a source file: a 1-based line number and
+/// a 0-based byte offset from the start of that line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineColumn {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Maps the byte offsets carried by `Token`/`LexError` spans back to
+/// human-facing line and column numbers. Built once per source file: it
+/// precomputes the byte offset of every line start so `line_col` can
+/// binary-search it in `O(log n)` instead of rescanning the source on every
+/// diagnostic.
+#[derive(Debug)]
+pub struct SourceMap<'a> {
+    source: &'a [u8],
+    line_starts: Vec<usize>,
+}
+
+impl<'a> SourceMap<'a> {
+    pub fn new(source: &'a [u8]) -> Self {
+        let mut line_starts = vec![0];
+        for (offset, &b) in source.iter().enumerate() {
+            if b == b'\n' {
+                line_starts.push(offset + 1);
+            }
+        }
+
+        Self { source, line_starts }
+    }
+
+    /// Index into `line_starts` of the line containing `offset`. `offset`
+    /// must already be clamped to `[0, self.source.len()]`.
+    fn line_index(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(idx) => idx,
+            Err(idx) => idx - 1,
+        }
+    }
+
+    /// Converts a byte offset into a 1-based line and 0-based column,
+    /// clamping out-of-range offsets to the end of the source.
+    pub fn line_col(&self, offset: usize) -> LineColumn {
+        let offset = offset.min(self.source.len());
+        let line_idx = self.line_index(offset);
+
+        LineColumn {
+            line: line_idx + 1,
+            column: offset - self.line_starts[line_idx],
+        }
+    }
+
+    /// The byte range covering the full line(s) that `span_start..span_end`
+    /// touches, trimming a trailing `\r` before the newline so callers can
+    /// slice the source and render an underline beneath it. Out-of-range
+    /// offsets are clamped to the end of the source.
+    pub fn line_range(&self, span_start: usize, span_end: usize) -> (usize, usize) {
+        let span_start = span_start.min(self.source.len());
+        let span_end = span_end.min(self.source.len());
+
+        let start_idx = self.line_index(span_start);
+        // `span_end` is exclusive, so the last byte the span actually
+        // touches is `span_end - 1`; without this, a span whose end lands
+        // exactly on the next line's start byte (e.g. a `Newline` token's
+        // own span) would be classified as belonging to that next line.
+        let end_idx = self.line_index(span_end.saturating_sub(1).max(span_start));
+
+        let range_start = self.line_starts[start_idx];
+        let mut range_end = self
+            .line_starts
+            .get(end_idx + 1)
+            .map_or(self.source.len(), |&next_line_start| next_line_start - 1);
+        if range_end > range_start && self.source[range_end - 1] == b'\r' {
+            range_end -= 1;
+        }
+
+        (range_start, range_end)
+    }
+}
+
+#[cfg(test)]
+mod number_lexing_tests {
+    use super::*;
+
+    fn lex_number(source: &[u8]) -> Result<Option<Token<'_>>, LexError> {
+        Lexer::new(source, 0).lex_number()
+    }
+
+    #[test]
+    fn hex_prefix_without_digits_errors() {
+        let err = lex_number(b"0x").unwrap_err();
+        assert_eq!(err.kind, LexErrorKind::MalformedRadixNumber);
+    }
+
+    #[test]
+    fn octal_prefix_without_digits_errors() {
+        let err = lex_number(b"0o").unwrap_err();
+        assert_eq!(err.kind, LexErrorKind::MalformedRadixNumber);
+    }
+
+    #[test]
+    fn binary_prefix_followed_by_non_digit_errors() {
+        let err = lex_number(b"0bz").unwrap_err();
+        assert_eq!(err.kind, LexErrorKind::MalformedRadixNumber);
+    }
+
+    #[test]
+    fn underscore_separator_between_digits_is_consumed() {
+        let token = lex_number(b"1_000").unwrap().unwrap();
+        assert_eq!(token.contents, b"1_000");
+        assert_eq!(token.number_kind, Some(NumberKind::Int(Radix::Decimal)));
+    }
+
+    #[test]
+    fn trailing_underscore_is_not_consumed() {
+        // No digit follows the `_`, so scanning stops before it and the `1`
+        // alone is the number.
+        let token = lex_number(b"1_").unwrap().unwrap();
+        assert_eq!(token.contents, b"1");
+    }
+
+    #[test]
+    fn doubled_underscore_is_not_consumed() {
+        let token = lex_number(b"1__2").unwrap().unwrap();
+        assert_eq!(token.contents, b"1");
+    }
+
+    #[test]
+    fn dot_dot_is_not_swallowed_as_a_decimal_point() {
+        // `1..2` is a range, not a float `1.` followed by `.2`.
+        let token = lex_number(b"1..2").unwrap().unwrap();
+        assert_eq!(token.contents, b"1");
+        assert_eq!(token.number_kind, Some(NumberKind::Int(Radix::Decimal)));
+    }
+
+    #[test]
+    fn dot_followed_by_digit_makes_a_float() {
+        let token = lex_number(b"1.5").unwrap().unwrap();
+        assert_eq!(token.contents, b"1.5");
+        assert_eq!(token.number_kind, Some(NumberKind::Float));
+    }
+}
+
+#[cfg(test)]
+mod grouping_tests {
+    use super::*;
+
+    fn stream(source: &[u8]) -> Result<Vec<TokenTree<'_>>, LexError> {
+        let mut lexer = Lexer::new(source, 0);
+        token_stream(&mut lexer)
+    }
+
+    #[test]
+    fn balanced_nested_delimiters_parse_into_groups() {
+        let trees = stream(b"([{}])").unwrap();
+        let TokenTree::Group(paren) = &trees[0] else {
+            panic!("expected a Group");
+        };
+        assert_eq!(paren.delimiter, Delimiter::Paren);
+        let TokenTree::Group(bracket) = &paren.tokens[0] else {
+            panic!("expected a Group");
+        };
+        assert_eq!(bracket.delimiter, Delimiter::Bracket);
+        let TokenTree::Group(brace) = &bracket.tokens[0] else {
+            panic!("expected a Group");
+        };
+        assert_eq!(brace.delimiter, Delimiter::Brace);
+        assert!(brace.tokens.is_empty());
+    }
+
+    #[test]
+    fn unterminated_group_errors() {
+        let err = stream(b"(foo").unwrap_err();
+        assert_eq!(err.kind, LexErrorKind::UnterminatedGroup(Delimiter::Paren));
+    }
+
+    #[test]
+    fn unterminated_group_records_incomplete_reason() {
+        let mut lexer = Lexer::new(b"(foo", 0);
+        token_stream(&mut lexer).unwrap_err();
+        assert_eq!(
+            lexer.incomplete_reason(),
+            Some(Incomplete::OpenDelimiter(Delimiter::Paren))
+        );
+    }
+
+    #[test]
+    fn mismatched_close_delimiter_errors() {
+        let err = stream(b"(foo]").unwrap_err();
+        assert_eq!(
+            err.kind,
+            LexErrorKind::MismatchedCloseDelimiter(Delimiter::Bracket)
+        );
+        assert!(err.opening_span.is_some());
+    }
+
+    #[test]
+    fn unmatched_top_level_close_delimiter_errors() {
+        let err = stream(b")").unwrap_err();
+        assert_eq!(
+            err.kind,
+            LexErrorKind::MismatchedCloseDelimiter(Delimiter::Paren)
+        );
+        assert!(err.opening_span.is_none());
+    }
+
+    #[test]
+    fn nesting_past_the_depth_limit_errors_instead_of_overflowing_the_stack() {
+        let source = "(".repeat(MAX_GROUP_NESTING_DEPTH + 1).into_bytes();
+        let err = stream(&source).unwrap_err();
+        assert_eq!(err.kind, LexErrorKind::NestingTooDeep);
+    }
+
+    #[test]
+    fn nesting_at_the_depth_limit_does_not_error_on_depth_alone() {
+        let mut source = "(".repeat(MAX_GROUP_NESTING_DEPTH).into_bytes();
+        source.extend(vec![b')'; MAX_GROUP_NESTING_DEPTH]);
+        assert!(stream(&source).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod source_map_tests {
+    use super::*;
+
+    #[test]
+    fn line_col_reports_1_based_line_and_0_based_column() {
+        let map = SourceMap::new(b"abc\ndef");
+        assert_eq!(map.line_col(0), LineColumn { line: 1, column: 0 });
+        assert_eq!(map.line_col(5), LineColumn { line: 2, column: 1 });
+    }
+
+    #[test]
+    fn line_range_for_a_span_ending_on_the_next_line_start_stays_on_its_own_line() {
+        // The span of the `\n` itself: span_end (4) lands exactly on the
+        // start of line 2, which must not pull line 2 into the range.
+        let map = SourceMap::new(b"abc\ndef");
+        assert_eq!(map.line_range(3, 4), (0, 3));
+    }
+
+    #[test]
+    fn line_range_for_a_span_within_one_line() {
+        let map = SourceMap::new(b"abc\ndef");
+        assert_eq!(map.line_range(0, 3), (0, 3));
+    }
+
+    #[test]
+    fn line_range_spanning_multiple_lines_covers_all_of_them() {
+        let map = SourceMap::new(b"abc\ndef\nghi");
+        assert_eq!(map.line_range(1, 6), (0, 7));
+    }
+
+    #[test]
+    fn line_range_trims_a_trailing_carriage_return() {
+        let map = SourceMap::new(b"abc\r\ndef");
+        assert_eq!(map.line_range(0, 3), (0, 3));
+    }
+
+    #[test]
+    fn crlf_line_boundaries_are_tracked_by_the_newline_byte() {
+        let map = SourceMap::new(b"ab\r\ncd");
+        assert_eq!(map.line_col(5), LineColumn { line: 2, column: 1 });
+    }
+}